@@ -0,0 +1,327 @@
+//! Lowering a grammar production to a [`regex::Regex`] so that strings can
+//! actually be tested against it, instead of the expression tree only ever
+//! being rendered as documentation.
+
+use super::{Characters, Expression, ExpressionKind, Grammar};
+use regex::Regex;
+use std::fmt;
+
+/// The maximum number of nested `Nt` expansions to follow before giving up.
+///
+/// Productions are allowed to reference each other, but a regex has no way
+/// to express unbounded recursion, so a grammar that is (directly or
+/// indirectly) recursive can't be compiled; this bounds how far `compile`
+/// will chase references before reporting that as an error instead of
+/// overflowing the stack.
+const MAX_RECURSION_DEPTH: usize = 64;
+
+/// An error encountered while compiling a production to a regex.
+#[derive(Debug)]
+pub enum CompileError {
+    /// The requested production, or one it references, is not defined.
+    UnknownProduction(String),
+    /// A `Prose` expression has no regular-language meaning. Carries the
+    /// name of the production it was found in (which may be one reached
+    /// through an `Nt` expansion, not necessarily the one passed to
+    /// `compile`), and the prose text.
+    Prose { production: String, text: String },
+    /// Expanding `Nt` references exceeded [`MAX_RECURSION_DEPTH`], which
+    /// means the production is (indirectly) recursive.
+    RecursionLimit(String),
+    /// A `Characters::Named` class that isn't registered.
+    UnknownCharacterClass(String),
+    /// A negation of something other than a charset or terminal, which has
+    /// no `[^...]` equivalent.
+    UnsupportedNegation,
+    /// The lowered pattern was rejected by the regex crate.
+    Regex(regex::Error),
+}
+
+impl fmt::Display for CompileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CompileError::UnknownProduction(name) => {
+                write!(f, "production `{name}` is not defined")
+            }
+            CompileError::Prose { production, text } => write!(
+                f,
+                "production `{production}` contains prose `<{text}>`, which has no \
+                 regular-language meaning and cannot be compiled to a regex"
+            ),
+            CompileError::RecursionLimit(name) => write!(
+                f,
+                "expanding `{name}` exceeded the recursion limit of {MAX_RECURSION_DEPTH}; \
+                 the grammar is likely recursive"
+            ),
+            CompileError::UnknownCharacterClass(name) => {
+                write!(f, "unknown named character class `{name}`")
+            }
+            CompileError::UnsupportedNegation => {
+                write!(f, "only a charset or terminal can be negated with `~`")
+            }
+            CompileError::Regex(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for CompileError {}
+
+impl From<regex::Error> for CompileError {
+    fn from(e: regex::Error) -> Self {
+        CompileError::Regex(e)
+    }
+}
+
+/// Maps a `Characters::Named` class (written as `[ DIGIT ]` etc. in a
+/// grammar source) to the regex class it lowers to. Only `ALPHA`, `DIGIT`,
+/// `HEXDIGIT`, and `WHITESPACE` are registered; any other name, such as
+/// `XID_Start`, returns `None`.
+fn named_class(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "ALPHA" => "a-zA-Z",
+        "DIGIT" => "0-9",
+        "HEXDIGIT" => "0-9a-fA-F",
+        "WHITESPACE" => r"\s",
+        _ => return None,
+    })
+}
+
+impl Grammar {
+    /// Compiles the named production to a `Regex` matching its language.
+    ///
+    /// `Nt` references are expanded inline, recursively, up to a bounded
+    /// depth; a cycle is reported as a [`CompileError`] rather than
+    /// recursing forever. `Prose` expressions have no regular-language
+    /// meaning and always produce an error naming the offending production.
+    pub fn compile(&self, name: &str) -> Result<Regex, CompileError> {
+        let production = self
+            .productions
+            .get(name)
+            .ok_or_else(|| CompileError::UnknownProduction(name.to_string()))?;
+        let mut pattern = String::from(r"\A(?:");
+        self.lower_expression(&production.expression, &mut pattern, 0, name)?;
+        pattern.push_str(r")\z");
+        Ok(Regex::new(&pattern)?)
+    }
+
+    fn lower_expression(
+        &self,
+        expression: &Expression,
+        pattern: &mut String,
+        depth: usize,
+        production: &str,
+    ) -> Result<(), CompileError> {
+        self.lower_kind(&expression.kind, pattern, depth, production)
+    }
+
+    fn lower_kind(
+        &self,
+        kind: &ExpressionKind,
+        pattern: &mut String,
+        depth: usize,
+        production: &str,
+    ) -> Result<(), CompileError> {
+        match kind {
+            ExpressionKind::Terminal(s) => pattern.push_str(&regex::escape(s)),
+            ExpressionKind::Charset(characters) => {
+                pattern.push('[');
+                for ch in characters {
+                    self.lower_characters(ch, pattern)?;
+                }
+                pattern.push(']');
+            }
+            ExpressionKind::NegExpression(inner) => match &inner.kind {
+                ExpressionKind::Charset(characters) => {
+                    pattern.push_str("[^");
+                    for ch in characters {
+                        self.lower_characters(ch, pattern)?;
+                    }
+                    pattern.push(']');
+                }
+                ExpressionKind::Terminal(s) => {
+                    pattern.push_str("[^");
+                    for c in s.chars() {
+                        escape_class_char(c, pattern);
+                    }
+                    pattern.push(']');
+                }
+                _ => return Err(CompileError::UnsupportedNegation),
+            },
+            ExpressionKind::Unicode(code) => pattern.push_str(&format!(r"\x{{{code}}}")),
+            ExpressionKind::Alt(es) => {
+                pattern.push_str("(?:");
+                for (i, e) in es.iter().enumerate() {
+                    if i > 0 {
+                        pattern.push('|');
+                    }
+                    self.lower_expression(e, pattern, depth, production)?;
+                }
+                pattern.push(')');
+            }
+            ExpressionKind::Sequence(es) => {
+                for e in es {
+                    self.lower_expression(e, pattern, depth, production)?;
+                }
+            }
+            // A `Break` is purely a formatting hint for rendering a
+            // production across multiple lines; it has no effect on what
+            // the production matches.
+            ExpressionKind::Break(_) => {}
+            ExpressionKind::Optional(e) => self.lower_quantified(e, pattern, depth, production, "?")?,
+            ExpressionKind::Repeat(e) => self.lower_quantified(e, pattern, depth, production, "*")?,
+            ExpressionKind::RepeatNonGreedy(e) => {
+                self.lower_quantified(e, pattern, depth, production, "*?")?
+            }
+            ExpressionKind::RepeatPlus(e) => self.lower_quantified(e, pattern, depth, production, "+")?,
+            ExpressionKind::RepeatPlusNonGreedy(e) => {
+                self.lower_quantified(e, pattern, depth, production, "+?")?
+            }
+            ExpressionKind::RepeatRange(e, a, b) => {
+                // The regex crate has no `{,b}` syntax for an omitted lower
+                // bound, so that case is written as an explicit `{0,b}`.
+                let range = match (a, b) {
+                    (Some(a), Some(b)) => format!("{{{a},{b}}}"),
+                    (Some(a), None) => format!("{{{a},}}"),
+                    (None, Some(b)) => format!("{{0,{b}}}"),
+                    (None, None) => "*".to_string(),
+                };
+                self.lower_quantified(e, pattern, depth, production, &range)?
+            }
+            ExpressionKind::Grouped(e) => {
+                pattern.push_str("(?:");
+                self.lower_expression(e, pattern, depth, production)?;
+                pattern.push(')');
+            }
+            ExpressionKind::Nt(name) => {
+                if depth >= MAX_RECURSION_DEPTH {
+                    return Err(CompileError::RecursionLimit(name.clone()));
+                }
+                let referenced = self
+                    .productions
+                    .get(name)
+                    .ok_or_else(|| CompileError::UnknownProduction(name.clone()))?;
+                pattern.push_str("(?:");
+                self.lower_expression(&referenced.expression, pattern, depth + 1, name)?;
+                pattern.push(')');
+            }
+            ExpressionKind::Prose(text) => {
+                return Err(CompileError::Prose {
+                    production: production.to_string(),
+                    text: text.clone(),
+                })
+            }
+        }
+        Ok(())
+    }
+
+    /// Lowers `e`, wraps it in a non-capturing group, and appends `suffix`
+    /// (a quantifier like `*` or an explicit `{a,b}` range).
+    fn lower_quantified(
+        &self,
+        e: &Expression,
+        pattern: &mut String,
+        depth: usize,
+        production: &str,
+        suffix: &str,
+    ) -> Result<(), CompileError> {
+        pattern.push_str("(?:");
+        self.lower_expression(e, pattern, depth, production)?;
+        pattern.push(')');
+        pattern.push_str(suffix);
+        Ok(())
+    }
+
+    fn lower_characters(
+        &self,
+        characters: &Characters,
+        pattern: &mut String,
+    ) -> Result<(), CompileError> {
+        match characters {
+            Characters::Terminal(s) => {
+                for c in s.chars() {
+                    escape_class_char(c, pattern);
+                }
+            }
+            Characters::Range(a, b) => {
+                escape_class_char(*a, pattern);
+                pattern.push('-');
+                escape_class_char(*b, pattern);
+            }
+            Characters::Named(name) => {
+                let class = named_class(name)
+                    .ok_or_else(|| CompileError::UnknownCharacterClass(name.clone()))?;
+                pattern.push_str(class);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Escapes a single character for safe inclusion inside a `[...]` character
+/// class, where `]`, `^`, `-`, and `\` are meta characters (unlike in a
+/// normal pattern position, where `regex::escape` is used instead).
+fn escape_class_char(c: char, pattern: &mut String) {
+    if matches!(c, ']' | '^' | '-' | '\\') {
+        pattern.push('\\');
+    }
+    pattern.push(c);
+}
+
+#[cfg(test)]
+use super::grammar_from;
+
+#[test]
+fn compile_matches_expected_strings() {
+    let grammar = grammar_from("Digit -> [`0`-`9`]\n\nNumber -> Digit+\n");
+    let re = grammar.compile("Number").unwrap();
+    assert!(re.is_match("123"));
+    assert!(!re.is_match("abc"));
+}
+
+#[test]
+fn compile_unknown_production_is_an_error() {
+    let grammar = grammar_from("Foo -> `a`\n");
+    assert!(matches!(
+        grammar.compile("Bar"),
+        Err(CompileError::UnknownProduction(name)) if name == "Bar"
+    ));
+}
+
+#[test]
+fn compile_prose_is_an_error() {
+    let grammar = grammar_from("Thing -> <anything>\n");
+    assert!(matches!(
+        grammar.compile("Thing"),
+        Err(CompileError::Prose { production, .. }) if production == "Thing"
+    ));
+}
+
+#[test]
+fn compile_prose_behind_nt_names_the_nested_production() {
+    let grammar = grammar_from("Outer -> Inner\n\nInner -> <anything>\n");
+    assert!(matches!(
+        grammar.compile("Outer"),
+        Err(CompileError::Prose { production, .. }) if production == "Inner"
+    ));
+}
+
+#[test]
+fn compile_is_fully_anchored() {
+    let grammar = grammar_from("Digit -> [`0`-`9`]+\n");
+    let re = grammar.compile("Digit").unwrap();
+    assert!(re.is_match("123"));
+    assert!(!re.is_match("123abc"));
+    assert!(!re.is_match("abc123"));
+}
+
+#[test]
+fn compile_charset_range_with_metacharacter_endpoint() {
+    // The range `!`..`]` includes `]`, a character class metacharacter, as
+    // its upper endpoint.
+    let grammar = grammar_from("Foo -> [`!`-`]`]\n");
+    let re = grammar.compile("Foo").unwrap();
+    assert!(re.is_match("!"));
+    assert!(re.is_match("]"));
+    assert!(re.is_match("#"));
+    assert!(!re.is_match(" "));
+}
@@ -0,0 +1,132 @@
+//! Support for the custom EBNF-like grammar used to define the Rust syntax
+//! throughout the reference.
+
+mod compile;
+mod parser;
+mod validate;
+
+use std::collections::BTreeMap;
+use std::ops::Range;
+use std::path::PathBuf;
+
+pub use compile::CompileError;
+pub use parser::{parse_grammar, Error};
+pub use validate::Diagnostic;
+
+/// The complete set of grammar productions collected from the book.
+///
+/// The `serde` feature requires `serde` (with the `derive` feature) and
+/// `serde_json` as optional dependencies, wired into a `serde` feature in
+/// this crate's `Cargo.toml`.
+#[derive(Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Grammar {
+    /// Map of production name to its definition.
+    pub productions: BTreeMap<String, Production>,
+    /// The order productions were defined in the source (the map above does
+    /// not preserve definition order).
+    pub name_order: Vec<String>,
+}
+
+/// A single named production, such as `IDENTIFIER -> ...`.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Production {
+    pub name: String,
+    pub category: String,
+    pub expression: Expression,
+    pub path: PathBuf,
+    pub is_root: bool,
+}
+
+/// A node in the expression tree making up the right-hand side of a
+/// production.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Expression {
+    pub kind: ExpressionKind,
+    pub suffix: Option<String>,
+    pub footnote: Option<String>,
+    /// The byte offsets of this node within the source it was parsed from.
+    pub span: Range<usize>,
+}
+
+/// The various kinds of expressions that can appear in a [`Expression`].
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ExpressionKind {
+    Alt(Vec<Expression>),
+    Sequence(Vec<Expression>),
+    Optional(Box<Expression>),
+    Repeat(Box<Expression>),
+    RepeatNonGreedy(Box<Expression>),
+    RepeatPlus(Box<Expression>),
+    RepeatPlusNonGreedy(Box<Expression>),
+    RepeatRange(Box<Expression>, Option<u32>, Option<u32>),
+    Charset(Vec<Characters>),
+    NegExpression(Box<Expression>),
+    Terminal(String),
+    Nt(String),
+    Prose(String),
+    Grouped(Box<Expression>),
+    Unicode(String),
+    Break(usize),
+}
+
+/// A single entry within a `[...]` character set.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Characters {
+    Named(String),
+    Terminal(String),
+    Range(char, char),
+}
+
+impl Grammar {
+    /// Converts a byte-offset `span` to `(line, lineno, col)`, reusing the
+    /// same position logic [`Error`] uses for parse failures.
+    ///
+    /// `source` must be the original text that `span` was recorded against.
+    pub fn span_position<'a>(&self, source: &'a str, span: &Range<usize>) -> (&'a str, usize, usize) {
+        parser::translate_position(source, span.start)
+    }
+
+    /// Serializes the grammar to a JSON string.
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("Grammar should always serialize")
+    }
+}
+
+/// Builds a [`Grammar`] from a single grammar source string, for use by
+/// this module's tests and its siblings' (`compile`, `validate`).
+#[cfg(test)]
+pub(crate) fn grammar_from(input: &str) -> Grammar {
+    let mut grammar = Grammar::default();
+    if let Err(errors) =
+        parser::parse_grammar(input, &mut grammar, "test", std::path::Path::new("test.md"))
+    {
+        let messages: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+        panic!("failed to parse test grammar:{}", messages.join(""));
+    }
+    grammar
+}
+
+#[test]
+fn span_covers_the_expression_text() {
+    let source = "Foo -> `a`\n";
+    let grammar = grammar_from(source);
+    let span = grammar.productions["Foo"].expression.span.clone();
+    assert_eq!(&source[span.clone()], "`a`");
+    assert_eq!(grammar.span_position(source, &span), ("Foo -> `a`", 1, 8));
+}
+
+#[cfg(all(test, feature = "serde"))]
+#[test]
+fn json_round_trip_preserves_name_order() {
+    let grammar = grammar_from("@root Second -> `b`\n\nFirst -> `a`\n");
+
+    let json = grammar.to_json();
+    let round_tripped: Grammar = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(round_tripped.name_order, grammar.name_order);
+    assert_eq!(round_tripped.name_order, vec!["Second".to_string(), "First".to_string()]);
+    assert!(round_tripped.productions.contains_key("First"));
+    assert!(round_tripped.productions["Second"].is_root);
+}
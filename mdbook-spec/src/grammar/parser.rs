@@ -1,4 +1,8 @@
 //! A parser of the ENBF-like grammar.
+//!
+//! Line comments (a line whose first non-space bytes are `//`) may appear
+//! between productions, or at the end of a line within a production, and
+//! are ignored.
 
 use super::{Characters, Expression, ExpressionKind, Grammar, Production};
 use regex::{Captures, Regex};
@@ -10,6 +14,12 @@ use std::sync::LazyLock;
 struct Parser<'a> {
     input: &'a str,
     index: usize,
+    /// Diagnostics accumulated so far in error-recovery mode.
+    ///
+    /// `parse_grammar` keeps parsing after a recoverable error so that a
+    /// single pass can report every independent problem in the grammar,
+    /// instead of forcing one edit-compile cycle per typo.
+    errors: Vec<Error>,
 }
 
 pub struct Error {
@@ -40,25 +50,60 @@ macro_rules! bail {
 
 type Result<T> = std::result::Result<T, Error>;
 
+/// Parses every production in `input`, adding them to `grammar`.
+///
+/// This runs in error-recovery mode: a problem with one production does not
+/// stop parsing of the rest of the grammar. Instead, the parser
+/// *synchronizes* by skipping ahead to what looks like the start of the next
+/// production and keeps going, so that a grammar file with several mistakes
+/// reports all of them in one pass. Only a genuinely unrecoverable state
+/// (running out of input without finding another production to resume at)
+/// stops parsing early.
 pub fn parse_grammar(
     input: &str,
     grammar: &mut Grammar,
     category: &str,
     path: &Path,
-) -> Result<()> {
-    let mut parser = Parser { input, index: 0 };
+) -> std::result::Result<(), Vec<Error>> {
+    let mut parser = Parser {
+        input,
+        index: 0,
+        errors: Vec::new(),
+    };
     loop {
-        let p = parser.parse_production(category, path)?;
-        grammar.name_order.push(p.name.clone());
-        if let Some(dupe) = grammar.productions.insert(p.name.clone(), p) {
-            bail!(parser, "duplicate production {} in grammar", dupe.name);
+        match parser.parse_production(category, path) {
+            Ok(p) => {
+                if grammar.productions.contains_key(&p.name) {
+                    let err = parser.error(format!("duplicate production {} in grammar", p.name));
+                    parser.errors.push(err);
+                } else {
+                    grammar.name_order.push(p.name.clone());
+                }
+                grammar.productions.insert(p.name.clone(), p);
+            }
+            Err(e) => {
+                parser.errors.push(e);
+                if !parser.synchronize() {
+                    break;
+                }
+            }
+        }
+        // Skip blank lines and comment-only lines between productions.
+        loop {
+            parser.skip_trivia();
+            if parser.take_while(&|ch| ch == '\n').is_empty() {
+                break;
+            }
         }
-        parser.take_while(&|ch| ch == '\n');
         if parser.eof() {
             break;
         }
     }
-    Ok(())
+    if parser.errors.is_empty() {
+        Ok(())
+    } else {
+        Err(parser.errors)
+    }
 }
 
 impl Parser<'_> {
@@ -135,6 +180,58 @@ impl Parser<'_> {
         self.take_while(&|ch| ch == ' ')
     }
 
+    /// Advances over spaces and an optional trailing line comment (`//` to
+    /// the end of the line).
+    ///
+    /// This deliberately does not consume the newline itself, so that the
+    /// indentation-sensitive line-break handling in `parse_expr1` still sees
+    /// it and records a `Break` the same way it would for a plain
+    /// multi-line production with no comment.
+    ///
+    /// This must only be used between productions or between elements of a
+    /// sequence, never inside a terminal, prose, or `_`-suffixed text, since
+    /// those can legitimately contain `//`.
+    fn skip_trivia(&mut self) {
+        static COMMENT_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^//[^\n]*").unwrap());
+        self.space0();
+        self.take_re(&COMMENT_RE);
+    }
+
+    /// Skips ahead to the start of what looks like the next production, so
+    /// that parsing can resume after a recoverable error.
+    ///
+    /// A line is considered the start of a production if it matches an
+    /// optional `@root` followed by a `parse_name`-shaped name, then ` ->`.
+    /// Returns `false` if no such line is found before the end of input,
+    /// which means the error is unrecoverable.
+    ///
+    /// Each loop iteration that doesn't return still advances `self.index`
+    /// by at least one byte, so it cannot spin forever on pathological
+    /// input.
+    fn synchronize(&mut self) -> bool {
+        static HEADER_RE: LazyLock<Regex> =
+            LazyLock::new(|| Regex::new(r"^(@root +)?[A-Za-z0-9_]+ ->").unwrap());
+        loop {
+            if self.eof() {
+                return false;
+            }
+            // Check the current position first: an error can leave `index`
+            // sitting right at the start of the next production (e.g. after
+            // consuming blank lines while failing to find an expression),
+            // in which case there's nothing to skip.
+            if HEADER_RE.is_match(&self.input[self.index..]) {
+                return true;
+            }
+            // Otherwise skip the rest of this line, plus its newline, and
+            // check again.
+            self.take_while(&|ch| ch != '\n');
+            if self.eof() {
+                return false;
+            }
+            self.index += 1;
+        }
+    }
+
     fn parse_production(&mut self, category: &str, path: &Path) -> Result<Production> {
         let is_root = self.parse_is_root();
         self.space0();
@@ -170,6 +267,7 @@ impl Parser<'_> {
     fn parse_expression(&mut self) -> Result<Option<Expression>> {
         static ALT_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^ *\| *").unwrap());
 
+        let start = self.index;
         let mut es = Vec::new();
         loop {
             let Some(e) = self.parse_seq()? else { break };
@@ -185,14 +283,16 @@ impl Parser<'_> {
                 kind: ExpressionKind::Alt(es),
                 suffix: None,
                 footnote: None,
+                span: start..self.index,
             })),
         }
     }
 
     fn parse_seq(&mut self) -> Result<Option<Expression>> {
+        let start = self.index;
         let mut es = Vec::new();
         loop {
-            self.space0();
+            self.skip_trivia();
             let Some(e) = self.parse_expr1()? else {
                 break;
             };
@@ -205,11 +305,13 @@ impl Parser<'_> {
                 kind: ExpressionKind::Sequence(es),
                 suffix: None,
                 footnote: None,
+                span: start..self.index,
             })),
         }
     }
 
     fn parse_expr1(&mut self) -> Result<Option<Expression>> {
+        let start = self.index;
         let Some(next) = self.peek() else {
             return Ok(None);
         };
@@ -246,18 +348,20 @@ impl Parser<'_> {
         } else {
             return Ok(None);
         };
+        let inner_end = self.index;
 
         static REPEAT_RE: LazyLock<Regex> =
             LazyLock::new(|| Regex::new(r"^ ?(\*\?|\+\?|\?|\*|\+)").unwrap());
         static RANGE_RE: LazyLock<Regex> =
             LazyLock::new(|| Regex::new(r"^\{([0-9]+)?\.\.([0-9]+)?\}").unwrap());
         if let Some(cap) = self.take_re(&REPEAT_RE) {
+            let inner = start..inner_end;
             kind = match &cap[1] {
-                "?" => ExpressionKind::Optional(box_kind(kind)),
-                "*" => ExpressionKind::Repeat(box_kind(kind)),
-                "*?" => ExpressionKind::RepeatNonGreedy(box_kind(kind)),
-                "+" => ExpressionKind::RepeatPlus(box_kind(kind)),
-                "+?" => ExpressionKind::RepeatPlusNonGreedy(box_kind(kind)),
+                "?" => ExpressionKind::Optional(box_kind(kind, inner)),
+                "*" => ExpressionKind::Repeat(box_kind(kind, inner)),
+                "*?" => ExpressionKind::RepeatNonGreedy(box_kind(kind, inner)),
+                "+" => ExpressionKind::RepeatPlus(box_kind(kind, inner)),
+                "+?" => ExpressionKind::RepeatPlusNonGreedy(box_kind(kind, inner)),
                 s => panic!("unexpected `{s}`"),
             };
         } else if let Some(cap) = self.take_re(&RANGE_RE) {
@@ -267,7 +371,7 @@ impl Parser<'_> {
                 (Some(a), Some(b)) if b < a => bail!(self, "range {a}..{b} is malformed"),
                 _ => {}
             }
-            kind = ExpressionKind::RepeatRange(box_kind(kind), a, b);
+            kind = ExpressionKind::RepeatRange(box_kind(kind, start..inner_end), a, b);
         }
 
         let suffix = self.parse_suffix()?;
@@ -277,6 +381,7 @@ impl Parser<'_> {
             kind,
             suffix,
             footnote,
+            span: start..self.index,
         }))
     }
 
@@ -348,6 +453,7 @@ impl Parser<'_> {
 
     fn parse_neg_expression(&mut self) -> Result<ExpressionKind> {
         self.expect("~", "expected ~")?;
+        let start = self.index;
         let Some(next) = self.peek() else {
             bail!(self, "expected expression after ~");
         };
@@ -358,7 +464,7 @@ impl Parser<'_> {
                 self.error("expected a charset, terminal, or name after ~ negation".to_string())
             })?,
         };
-        Ok(ExpressionKind::NegExpression(box_kind(kind)))
+        Ok(ExpressionKind::NegExpression(box_kind(kind, start..self.index)))
     }
 
     fn parse_unicode(&mut self) -> Result<ExpressionKind> {
@@ -412,16 +518,17 @@ impl Parser<'_> {
     }
 }
 
-fn box_kind(kind: ExpressionKind) -> Box<Expression> {
+fn box_kind(kind: ExpressionKind, span: std::ops::Range<usize>) -> Box<Expression> {
     Box::new(Expression {
         kind,
         suffix: None,
         footnote: None,
+        span,
     })
 }
 
 /// Helper to translate a byte index to a `(line, line_no, col_no)` (1-based).
-fn translate_position(input: &str, index: usize) -> (&str, usize, usize) {
+pub(super) fn translate_position(input: &str, index: usize) -> (&str, usize, usize) {
     if input.is_empty() {
         return ("", 0, 0);
     }
@@ -451,3 +558,35 @@ fn translate_tests() {
     assert_eq!(translate_position("test\ntest2", 5), ("test2", 2, 1));
     assert_eq!(translate_position("test\ntest2\n", 11), ("", 3, 0));
 }
+
+#[test]
+fn recovers_across_root_boundary() {
+    let mut grammar = Grammar::default();
+    let input = "Bad ->\n\n@root Good -> `x`\n\nThird -> `y`\n";
+    let result = parse_grammar(input, &mut grammar, "test", Path::new("test.md"));
+    assert!(result.is_err());
+    assert_eq!(grammar.name_order, vec!["Good".to_string(), "Third".to_string()]);
+    assert!(grammar.productions["Good"].is_root);
+}
+
+#[test]
+fn duplicate_production_is_recorded_once_in_name_order() {
+    let mut grammar = Grammar::default();
+    let input = "Foo -> `a`\n\nFoo -> `b`\n";
+    let result = parse_grammar(input, &mut grammar, "test", Path::new("test.md"));
+    assert!(result.is_err());
+    assert_eq!(grammar.name_order, vec!["Foo".to_string()]);
+}
+
+#[test]
+fn comments_are_skipped() {
+    let mut grammar = Grammar::default();
+    let input = "Foo -> `a` // trailing comment\n\n// standalone comment\nBar -> `b`\n";
+    let result = parse_grammar(input, &mut grammar, "test", Path::new("test.md"));
+    assert!(result.is_ok());
+    assert_eq!(grammar.name_order, vec!["Foo".to_string(), "Bar".to_string()]);
+    assert!(matches!(
+        grammar.productions["Foo"].expression.kind,
+        ExpressionKind::Terminal(ref s) if s == "a"
+    ));
+}
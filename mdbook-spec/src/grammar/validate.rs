@@ -0,0 +1,271 @@
+//! Static validation of a parsed [`Grammar`]: references to undefined
+//! nonterminals, productions that are never reached, and left-recursive
+//! cycles.
+
+use super::{Expression, ExpressionKind, Grammar};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+/// A single problem found by [`Grammar::validate`].
+pub enum Diagnostic {
+    /// `production` references a nonterminal that isn't defined anywhere in
+    /// the grammar.
+    UndefinedNonterminal { production: String, reference: String },
+    /// `production` isn't marked `@root` and can't be reached by following
+    /// references from any `@root` production (for example, it is only
+    /// referenced by other unreachable productions, or not at all).
+    UnusedProduction { production: String },
+    /// A left-recursive cycle: each production in `cycle` can begin by
+    /// matching the next one, and the last entry repeats the first.
+    LeftRecursion { cycle: Vec<String> },
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Diagnostic::UndefinedNonterminal {
+                production,
+                reference,
+            } => write!(
+                f,
+                "production `{production}` references undefined nonterminal `{reference}`"
+            ),
+            Diagnostic::UnusedProduction { production } => {
+                write!(f, "production `{production}` is never used")
+            }
+            Diagnostic::LeftRecursion { cycle } => {
+                write!(f, "left-recursive cycle: {}", cycle.join(" -> "))
+            }
+        }
+    }
+}
+
+impl Grammar {
+    /// Runs every static check against the grammar and returns all the
+    /// problems found.
+    pub fn validate(&self) -> Vec<Diagnostic> {
+        let mut diagnostics = self.validate_undefined_nonterminals();
+        diagnostics.extend(self.validate_unused_productions());
+        diagnostics.extend(self.validate_left_recursion());
+        diagnostics
+    }
+
+    fn validate_undefined_nonterminals(&self) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        for name in &self.name_order {
+            // `name_order` and `productions` are built together by the
+            // parser, but a deserialized `Grammar` isn't guaranteed to keep
+            // them in sync, so this tolerates a stale entry rather than
+            // panicking.
+            let Some(production) = self.productions.get(name) else {
+                continue;
+            };
+            let mut references = Vec::new();
+            collect_references(&production.expression, &mut references);
+            for reference in references {
+                if !self.productions.contains_key(&reference) {
+                    diagnostics.push(Diagnostic::UndefinedNonterminal {
+                        production: name.clone(),
+                        reference,
+                    });
+                }
+            }
+        }
+        diagnostics
+    }
+
+    /// Dead-rule detection: walk every reference reachable from an `@root`
+    /// production, and report anything left over.
+    fn validate_unused_productions(&self) -> Vec<Diagnostic> {
+        let mut reachable: HashSet<String> = HashSet::new();
+        let mut stack: Vec<String> = self
+            .productions
+            .values()
+            .filter(|p| p.is_root)
+            .map(|p| p.name.clone())
+            .collect();
+        while let Some(name) = stack.pop() {
+            if !reachable.insert(name.clone()) {
+                continue;
+            }
+            if let Some(production) = self.productions.get(&name) {
+                let mut references = Vec::new();
+                collect_references(&production.expression, &mut references);
+                stack.extend(references);
+            }
+        }
+        self.name_order
+            .iter()
+            .filter(|name| !reachable.contains(*name))
+            .map(|name| Diagnostic::UnusedProduction {
+                production: name.clone(),
+            })
+            .collect()
+    }
+
+    /// Builds a graph where an edge `A -> B` exists when `B` can appear as
+    /// the leftmost symbol of `A`, then runs a DFS with a recursion stack to
+    /// find back-edges.
+    fn validate_left_recursion(&self) -> Vec<Diagnostic> {
+        let mut edges: HashMap<&str, Vec<String>> = HashMap::new();
+        for name in &self.name_order {
+            let Some(production) = self.productions.get(name) else {
+                continue;
+            };
+            let mut leftmost = Vec::new();
+            leftmost_nonterminals(&production.expression, &mut leftmost);
+            edges.insert(name.as_str(), leftmost);
+        }
+
+        let mut diagnostics = Vec::new();
+        let mut visited: HashSet<String> = HashSet::new();
+        for name in &self.name_order {
+            if !visited.contains(name) {
+                let mut stack = Vec::new();
+                find_left_recursion(name, &edges, &mut visited, &mut stack, &mut diagnostics);
+            }
+        }
+        diagnostics
+    }
+}
+
+fn find_left_recursion(
+    name: &str,
+    edges: &HashMap<&str, Vec<String>>,
+    visited: &mut HashSet<String>,
+    stack: &mut Vec<String>,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    if let Some(pos) = stack.iter().position(|n| n == name) {
+        let mut cycle = stack[pos..].to_vec();
+        cycle.push(name.to_string());
+        diagnostics.push(Diagnostic::LeftRecursion { cycle });
+        return;
+    }
+    if !visited.insert(name.to_string()) {
+        return;
+    }
+    stack.push(name.to_string());
+    if let Some(references) = edges.get(name) {
+        for reference in references {
+            find_left_recursion(reference, edges, visited, stack, diagnostics);
+        }
+    }
+    stack.pop();
+}
+
+/// Collects every `Nt` name referenced anywhere in `expression`.
+fn collect_references(expression: &Expression, out: &mut Vec<String>) {
+    collect_references_kind(&expression.kind, out);
+}
+
+fn collect_references_kind(kind: &ExpressionKind, out: &mut Vec<String>) {
+    match kind {
+        ExpressionKind::Nt(name) => out.push(name.clone()),
+        ExpressionKind::Alt(es) | ExpressionKind::Sequence(es) => {
+            for e in es {
+                collect_references(e, out);
+            }
+        }
+        ExpressionKind::Optional(e)
+        | ExpressionKind::Repeat(e)
+        | ExpressionKind::RepeatNonGreedy(e)
+        | ExpressionKind::RepeatPlus(e)
+        | ExpressionKind::RepeatPlusNonGreedy(e)
+        | ExpressionKind::RepeatRange(e, _, _)
+        | ExpressionKind::Grouped(e)
+        | ExpressionKind::NegExpression(e) => collect_references(e, out),
+        ExpressionKind::Charset(_)
+        | ExpressionKind::Terminal(_)
+        | ExpressionKind::Prose(_)
+        | ExpressionKind::Unicode(_)
+        | ExpressionKind::Break(_) => {}
+    }
+}
+
+/// Returns the nonterminals that could be the leftmost symbol matched by
+/// `expression`, used to build the left-recursion graph.
+fn leftmost_nonterminals(expression: &Expression, out: &mut Vec<String>) {
+    leftmost_nonterminals_kind(&expression.kind, out);
+}
+
+fn leftmost_nonterminals_kind(kind: &ExpressionKind, out: &mut Vec<String>) {
+    match kind {
+        ExpressionKind::Nt(name) => out.push(name.clone()),
+        ExpressionKind::Alt(es) => {
+            for e in es {
+                leftmost_nonterminals(e, out);
+            }
+        }
+        ExpressionKind::Sequence(es) => {
+            for e in es {
+                leftmost_nonterminals(e, out);
+                if !can_be_empty(&e.kind) {
+                    break;
+                }
+            }
+        }
+        ExpressionKind::Optional(e)
+        | ExpressionKind::Repeat(e)
+        | ExpressionKind::RepeatNonGreedy(e)
+        | ExpressionKind::RepeatPlus(e)
+        | ExpressionKind::RepeatPlusNonGreedy(e)
+        | ExpressionKind::RepeatRange(e, _, _)
+        | ExpressionKind::Grouped(e) => leftmost_nonterminals(e, out),
+        ExpressionKind::NegExpression(_)
+        | ExpressionKind::Charset(_)
+        | ExpressionKind::Terminal(_)
+        | ExpressionKind::Prose(_)
+        | ExpressionKind::Unicode(_)
+        | ExpressionKind::Break(_) => {}
+    }
+}
+
+/// Whether `kind` can match the empty string, and so exposes whatever
+/// follows it in a `Sequence` as also being leftmost.
+fn can_be_empty(kind: &ExpressionKind) -> bool {
+    match kind {
+        ExpressionKind::Optional(_) | ExpressionKind::Repeat(_) | ExpressionKind::RepeatNonGreedy(_) => {
+            true
+        }
+        ExpressionKind::RepeatRange(_, min, _) => min.unwrap_or(0) == 0,
+        // A `Break` is a zero-width line-break hint for rendering.
+        ExpressionKind::Break(_) => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+use super::grammar_from;
+
+#[test]
+fn validate_reports_undefined_nonterminal() {
+    let grammar = grammar_from("@root Foo -> Bar\n");
+    let diagnostics = grammar.validate();
+    assert!(diagnostics.iter().any(|d| matches!(
+        d,
+        Diagnostic::UndefinedNonterminal { production, reference }
+            if production == "Foo" && reference == "Bar"
+    )));
+}
+
+#[test]
+fn validate_reports_unused_production() {
+    let grammar = grammar_from("@root Foo -> `a`\n\nUnused -> `b`\n");
+    let diagnostics = grammar.validate();
+    assert!(diagnostics
+        .iter()
+        .any(|d| matches!(d, Diagnostic::UnusedProduction { production } if production == "Unused")));
+    assert!(!diagnostics
+        .iter()
+        .any(|d| matches!(d, Diagnostic::UnusedProduction { production } if production == "Foo")));
+}
+
+#[test]
+fn validate_reports_left_recursion() {
+    let grammar = grammar_from("@root Foo -> Foo `a`\n");
+    let diagnostics = grammar.validate();
+    assert!(diagnostics
+        .iter()
+        .any(|d| matches!(d, Diagnostic::LeftRecursion { cycle } if cycle == &["Foo".to_string(), "Foo".to_string()])));
+}